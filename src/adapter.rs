@@ -0,0 +1,78 @@
+use anyhow::{Result, anyhow};
+use std::{fmt, path::PathBuf};
+
+/// Which flavor of the embedded WASI preview1 adapter to fall back to when
+/// no `--adapter` override is given.
+#[derive(Clone, Copy, Debug, Default, clap::ValueEnum)]
+pub enum AdapterKind {
+    /// For components that export functions to be called repeatedly, e.g. an
+    /// `incoming-handler`.
+    #[default]
+    Reactor,
+    /// For components with a single `_start` entry point.
+    Command,
+}
+
+impl fmt::Display for AdapterKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AdapterKind::Reactor => write!(f, "reactor"),
+            AdapterKind::Command => write!(f, "command"),
+        }
+    }
+}
+
+/// A user-supplied adapter binary, keyed by the import module name it
+/// replaces (e.g. `wasi_snapshot_preview1`).
+pub struct AdapterSpec {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl AdapterSpec {
+    /// Parse `--adapter name=path` values.
+    pub fn parse_all(entries: &[String]) -> Result<Vec<Self>> {
+        entries
+            .iter()
+            .map(|entry| {
+                let (name, path) = entry.split_once('=').ok_or_else(|| {
+                    anyhow!("invalid --adapter value '{entry}', expected 'name=path'")
+                })?;
+                Ok(Self {
+                    name: name.to_string(),
+                    path: PathBuf::from(path),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_name_equals_path_pairs() {
+        let specs = AdapterSpec::parse_all(&[
+            "wasi_snapshot_preview1=./adapters/reactor.wasm".to_string(),
+            "wasi_snapshot_preview1_command=./adapters/command.wasm".to_string(),
+        ])
+        .unwrap();
+
+        assert_eq!(specs.len(), 2);
+        assert_eq!(specs[0].name, "wasi_snapshot_preview1");
+        assert_eq!(specs[0].path, PathBuf::from("./adapters/reactor.wasm"));
+        assert_eq!(specs[1].name, "wasi_snapshot_preview1_command");
+        assert_eq!(specs[1].path, PathBuf::from("./adapters/command.wasm"));
+    }
+
+    #[test]
+    fn empty_input_yields_no_adapters() {
+        assert!(AdapterSpec::parse_all(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_an_entry_without_an_equals_sign() {
+        assert!(AdapterSpec::parse_all(&["wasi_snapshot_preview1".to_string()]).is_err());
+    }
+}
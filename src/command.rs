@@ -1,5 +1,10 @@
+use crate::adapter::{AdapterKind, AdapterSpec};
 use crate::componentize;
-use anyhow::Result;
+use crate::publish::{self, ComponentRef};
+use crate::registry::RegistryMapping;
+use crate::scaffold::{self, Template};
+use crate::utils::make_path_absolute;
+use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use std::{ffi::OsString, path::PathBuf};
 
@@ -39,12 +44,42 @@ pub struct Common {
     /// This enables using `@unstable` annotations in WIT files.
     #[arg(long)]
     pub features: Vec<String>,
+
+    /// Maps a WIT package namespace to the OCI registry host that serves it,
+    /// e.g. `--registry wasi=ghcr.io/webassembly`.
+    ///
+    /// May be specified more than once. Required for any `world`/`import`
+    /// that references a package not found under `--wit-path`.
+    #[arg(long)]
+    pub registry: Vec<String>,
+
+    /// Fail instead of adding to or updating `componentize-go.lock`.
+    ///
+    /// Use this in CI to ensure builds only ever resolve WIT dependencies
+    /// that have already been pinned.
+    #[arg(long)]
+    pub frozen: bool,
+
+    /// Re-resolve a registry package even though it's already pinned,
+    /// rewriting its entry in `componentize-go.lock`.
+    ///
+    /// Pass one or more `namespace:name` packages to update just those, or
+    /// give `--update` with no value to update everything. Conflicts with
+    /// `--frozen`.
+    #[arg(long, num_args = 0..)]
+    pub update: Option<Vec<String>>,
 }
 
 #[derive(Subcommand)]
 pub enum Command {
     /// Build a Go WebAssembly component.
     Componentize(Componentize),
+
+    /// Push a built component to an OCI registry.
+    Publish(Publish),
+
+    /// Scaffold a new Go component project.
+    New(New),
 }
 
 #[derive(Parser)]
@@ -60,16 +95,93 @@ pub struct Componentize {
     /// The directory containing the "go.mod" file (or current directory if `None`).
     #[arg(long = "mod")]
     pub mod_path: Option<PathBuf>,
+
+    /// Prune functions unreachable from the component's exports and the WASI
+    /// adapter's entry points.
+    #[arg(long)]
+    pub optimize: bool,
+
+    /// Strip non-essential custom sections (debug info, producers) from the
+    /// component. Implies `--optimize`.
+    #[arg(long)]
+    pub strip: bool,
+
+    /// Use a WASI adapter from disk, as `name=path`.
+    ///
+    /// May be specified more than once. When given, only the listed adapters
+    /// are registered; the embedded WASI preview1 snapshot (registered as
+    /// `wasi_snapshot_preview1`) is used only when no `--adapter` is given.
+    #[arg(long = "adapter")]
+    pub adapters: Vec<String>,
+
+    /// Which embedded WASI preview1 snapshot to use when no `--adapter` is given.
+    #[arg(long, value_enum, default_value_t = AdapterKind::Reactor)]
+    pub adapter_kind: AdapterKind,
+}
+
+#[derive(Parser)]
+pub struct Publish {
+    /// The component to publish (or `./main.wasm` if `None`).
+    #[arg(long, short = 'c')]
+    pub component: Option<PathBuf>,
+
+    /// The reference to publish under, as `namespace:name@version`.
+    pub reference: String,
+
+    /// The OCI registry host to push to.
+    #[arg(long)]
+    pub registry: String,
+
+    /// Compute and report the manifest digest and layer sizes without uploading.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Parser)]
+pub struct New {
+    /// The directory to scaffold the project into.
+    pub dir: PathBuf,
+
+    /// The Go module name to put in "go.mod" (or the directory name if `None`).
+    #[arg(long)]
+    pub module: Option<String>,
+
+    /// Name of the world to scaffold.
+    #[arg(long, short = 'w', default_value = "app")]
+    pub world: String,
+
+    /// Which skeleton to generate.
+    #[arg(long, value_enum, default_value_t = Template::Wasip2)]
+    pub template: Template,
+
+    /// The path to the Go binary (or look for binary in PATH if `None`), used
+    /// to run `go mod tidy`.
+    #[arg(long)]
+    pub go: Option<PathBuf>,
 }
 
 pub fn run<T: Into<OsString> + Clone, I: IntoIterator<Item = T>>(args: I) -> Result<()> {
     let options = Options::parse_from(args);
     match options.command {
         Command::Componentize(opts) => componentize(options.common, opts),
+        Command::Publish(opts) => publish(opts),
+        Command::New(opts) => new(opts),
     }
 }
 
 fn componentize(common: Common, componentize: Componentize) -> Result<()> {
+    if common.frozen && common.update.is_some() {
+        return Err(anyhow::anyhow!("--frozen and --update cannot be used together"));
+    }
+
+    let mod_path = make_path_absolute(
+        &componentize
+            .mod_path
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(".")),
+    )?;
+    let registry = RegistryMapping::parse(&common.registry)?;
+
     // Step 1: Build a WebAssembly core module using Go.
     let core_module = componentize::build_wasm_core_module(
         componentize.mod_path,
@@ -84,9 +196,56 @@ fn componentize(common: Common, componentize: Componentize) -> Result<()> {
         common.world.as_deref(),
         &common.features,
         common.all_features,
+        &registry,
+        &mod_path,
+        common.frozen,
+        common.update.as_deref(),
     )?;
 
-    // Step 3: Update the core module to use the component model ABI.
-    componentize::core_module_to_component(&core_module)?;
+    // Step 3: Optionally prune dead code and strip debug info. This must run
+    // on the flat core module, before encoding it as a component: `optimize`
+    // only understands top-level core-module sections (import/export/code),
+    // and none of those appear at a component's top level once the adapter
+    // is embedded (the core module ends up nested inside a `Payload::ModuleSection`).
+    if componentize.optimize || componentize.strip {
+        let wasm = std::fs::read(&core_module)
+            .context(format!("failed to read '{}'", core_module.display()))?;
+        let optimized = crate::optimize::optimize(&wasm, componentize.strip)?;
+        std::fs::write(&core_module, optimized)
+            .context(format!("failed to write '{}'", core_module.display()))?;
+    }
+
+    // Step 4: Update the core module to use the component model ABI.
+    let adapters = AdapterSpec::parse_all(&componentize.adapters)?;
+    componentize::core_module_to_component(&core_module, &adapters, componentize.adapter_kind)?;
+
     Ok(())
 }
+
+fn publish(opts: Publish) -> Result<()> {
+    let component = opts.component.unwrap_or_else(|| PathBuf::from("main.wasm"));
+    let component_ref = ComponentRef::parse(&opts.reference, opts.registry)?;
+
+    let summary = publish::publish(&component, &component_ref, opts.dry_run)?;
+
+    if opts.dry_run {
+        println!("manifest digest: {}", summary.manifest_digest);
+        for (i, size) in summary.layer_sizes.iter().enumerate() {
+            println!("layer {i}: {size} bytes");
+        }
+    }
+
+    Ok(())
+}
+
+fn new(opts: New) -> Result<()> {
+    let module = opts.module.unwrap_or_else(|| {
+        opts.dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("app")
+            .to_string()
+    });
+
+    scaffold::new(&opts.dir, &module, &opts.world, opts.template, opts.go.as_ref())
+}
@@ -0,0 +1,172 @@
+use crate::adapter::{AdapterKind, AdapterSpec};
+use crate::registry::RegistryMapping;
+use crate::utils::{check_go_version, make_path_absolute};
+use anyhow::{Context, Result};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+use wit_parser::{PackageId, Resolve, WorldId};
+
+/// Build the Go package at `mod_path` (or the current directory) into a
+/// WebAssembly core module, returning the path to the resulting `.wasm` file.
+pub fn build_wasm_core_module(
+    mod_path: Option<PathBuf>,
+    output: Option<PathBuf>,
+    go: Option<PathBuf>,
+) -> Result<PathBuf> {
+    let mod_path = make_path_absolute(&mod_path.unwrap_or_else(|| PathBuf::from(".")))?;
+    let output = make_path_absolute(&output.unwrap_or_else(|| PathBuf::from("main.wasm")))?;
+    let go_path = go.unwrap_or_else(|| PathBuf::from("go"));
+
+    check_go_version(&go_path)?;
+
+    let build_output = Command::new(&go_path)
+        .env("GOOS", "wasip1")
+        .env("GOARCH", "wasm")
+        .arg("build")
+        .arg("-o")
+        .arg(&output)
+        .current_dir(&mod_path)
+        .output()
+        .context(format!("failed to execute '{}'", go_path.display()))?;
+
+    if !build_output.status.success() {
+        return Err(anyhow::anyhow!(
+            "'go build' failed: {}",
+            String::from_utf8_lossy(&build_output.stderr)
+        ));
+    }
+
+    Ok(output)
+}
+
+pub fn parse_wit(
+    paths: &[PathBuf],
+    world: Option<&str>,
+    features: &[String],
+    all_features: bool,
+    registry: &RegistryMapping,
+    mod_path: &Path,
+    frozen: bool,
+    update: Option<&[String]>,
+) -> Result<(Resolve, WorldId)> {
+    // If no WIT directory was provided as a parameter and none were referenced
+    // by Go packages, use ./wit by default.
+    if paths.is_empty() {
+        let paths = &[PathBuf::from("wit")];
+        return parse_wit(
+            paths,
+            world,
+            features,
+            all_features,
+            registry,
+            mod_path,
+            frozen,
+            update,
+        );
+    }
+    debug_assert!(!paths.is_empty(), "The paths should not be empty");
+
+    let mut resolve = Resolve {
+        all_features,
+        ..Default::default()
+    };
+    for features in features {
+        for feature in features
+            .split(',')
+            .flat_map(|s| s.split_whitespace())
+            .filter(|f| !f.is_empty())
+        {
+            resolve.features.insert(feature.to_string());
+        }
+    }
+
+    // Pull in any packages referenced by `world`/`import` but not present
+    // under `paths`, pinning them in `componentize-go.lock`.
+    let main_packages: Vec<PackageId> =
+        crate::registry::resolve_paths(&mut resolve, paths, registry, mod_path, frozen, update)?;
+
+    let world = resolve.select_world(&main_packages, world)?;
+    Ok((resolve, world))
+}
+
+pub fn embed_wit(
+    wasm_file: &PathBuf,
+    wit_path: &[PathBuf],
+    world: Option<&str>,
+    features: &[String],
+    all_features: bool,
+    registry: &RegistryMapping,
+    mod_path: &Path,
+    frozen: bool,
+    update: Option<&[String]>,
+) -> Result<()> {
+    let mut wasm = wat::Parser::new().parse_file(wasm_file)?;
+    let (resolve, world_id) = parse_wit(
+        wit_path,
+        world,
+        features,
+        all_features,
+        registry,
+        mod_path,
+        frozen,
+        update,
+    )?;
+    wit_component::embed_component_metadata(
+        &mut wasm,
+        &resolve,
+        world_id,
+        wit_component::StringEncoding::UTF8,
+    )?;
+    std::fs::write(wasm_file, wasm)
+        .context(format!("failed to write '{}'", wasm_file.display()))?;
+    Ok(())
+}
+
+/// Update the wasm module to use the current component model ABI.
+///
+/// Without `adapters`, the embedded WASI preview1 snapshot matching
+/// `adapter_kind` is registered as the `wasi_snapshot_preview1` adapter.
+/// Otherwise, each `AdapterSpec` is read from disk and registered under its
+/// own name and the embedded snapshot is not registered, letting callers
+/// replace the adapters without rebuilding this crate (e.g. as WASI versions evolve past the `wasip3` TODO in
+/// `check_go_version`).
+pub fn core_module_to_component(
+    wasm_file: &PathBuf,
+    adapters: &[AdapterSpec],
+    adapter_kind: AdapterKind,
+) -> Result<()> {
+    // In the rare case the snapshots need to be updated, the latest versions
+    // can be found here: https://github.com/bytecodealliance/wasmtime/releases
+    const WASIP1_REACTOR: &[u8] = include_bytes!("wasi_snapshot_preview1.reactor.wasm");
+    const WASIP1_COMMAND: &[u8] = include_bytes!("wasi_snapshot_preview1.command.wasm");
+
+    let wasm: Vec<u8> = wat::Parser::new().parse_file(wasm_file)?;
+
+    let mut encoder = wit_component::ComponentEncoder::default().validate(true);
+    encoder = encoder.module(&wasm)?;
+
+    if adapters.is_empty() {
+        let default_adapter = match adapter_kind {
+            AdapterKind::Reactor => WASIP1_REACTOR,
+            AdapterKind::Command => WASIP1_COMMAND,
+        };
+        encoder = encoder.adapter("wasi_snapshot_preview1", default_adapter)?;
+    } else {
+        for adapter in adapters {
+            let bytes = std::fs::read(&adapter.path)
+                .context(format!("failed to read '{}'", adapter.path.display()))?;
+            encoder = encoder.adapter(&adapter.name, &bytes)?;
+        }
+    }
+
+    let bytes = encoder
+        .encode()
+        .context("failed to encode component from module")?;
+
+    std::fs::write(wasm_file, bytes)
+        .context(format!("failed to write `{}`", wasm_file.display()))?;
+
+    Ok(())
+}
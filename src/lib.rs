@@ -0,0 +1,9 @@
+pub mod adapter;
+pub mod command;
+pub mod componentize;
+pub mod lockfile;
+pub mod optimize;
+pub mod publish;
+pub mod registry;
+pub mod scaffold;
+pub mod utils;
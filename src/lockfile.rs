@@ -0,0 +1,129 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// Name of the lockfile written next to `go.mod`.
+pub const FILE_NAME: &str = "componentize-go.lock";
+
+/// A pinned set of WIT packages resolved from remote registries.
+///
+/// Mirrors `Cargo.lock`'s role: once a package is recorded here, later runs
+/// pin to the same version and verify the registry returns the same bytes.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Lockfile {
+    #[serde(rename = "package", default)]
+    pub packages: BTreeMap<String, LockedPackage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockedPackage {
+    pub version: String,
+    pub source: String,
+    pub digest: String,
+}
+
+impl Lockfile {
+    /// Path of the lockfile for a project whose `go.mod` lives in `mod_path`.
+    pub fn path_for(mod_path: &Path) -> PathBuf {
+        mod_path.join(FILE_NAME)
+    }
+
+    /// Load the lockfile next to `go.mod`, or `None` if it doesn't exist yet.
+    pub fn load(mod_path: &Path) -> Result<Option<Self>> {
+        let path = Self::path_for(mod_path);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(&path)
+            .context(format!("failed to read '{}'", path.display()))?;
+        let lockfile = toml::from_str(&contents)
+            .context(format!("failed to parse '{}'", path.display()))?;
+        Ok(Some(lockfile))
+    }
+
+    /// Write the lockfile next to `go.mod`, overwriting any existing one.
+    pub fn write(&self, mod_path: &Path) -> Result<()> {
+        let path = Self::path_for(mod_path);
+        let contents = toml::to_string_pretty(self).context("failed to serialize lockfile")?;
+        std::fs::write(&path, contents).context(format!("failed to write '{}'", path.display()))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LockedPackage> {
+        self.packages.get(name)
+    }
+
+    pub fn insert(&mut self, name: String, package: LockedPackage) {
+        self.packages.insert(name, package);
+    }
+}
+
+/// Compute the `sha256:<hex>` content digest used to pin lockfile entries.
+pub fn digest(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    format!("sha256:{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digest_is_stable_and_sha256_prefixed() {
+        let a = digest(b"hello");
+        let b = digest(b"hello");
+        assert_eq!(a, b);
+        assert!(a.starts_with("sha256:"));
+        assert_eq!(a.trim_start_matches("sha256:").len(), 64);
+    }
+
+    #[test]
+    fn digest_differs_for_different_bytes() {
+        assert_ne!(digest(b"hello"), digest(b"world"));
+    }
+
+    #[test]
+    fn write_then_load_round_trips_packages() {
+        let dir = std::env::temp_dir().join(format!(
+            "componentize-go-lockfile-test-{:?}",
+            std::thread::current().id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut lockfile = Lockfile::default();
+        lockfile.insert(
+            "wasi:http".to_string(),
+            LockedPackage {
+                version: "0.2.0".to_string(),
+                source: "ghcr.io/webassembly".to_string(),
+                digest: digest(b"package bytes"),
+            },
+        );
+        lockfile.write(&dir).unwrap();
+
+        let loaded = Lockfile::load(&dir).unwrap().unwrap();
+        let package = loaded.get("wasi:http").unwrap();
+        assert_eq!(package.version, "0.2.0");
+        assert_eq!(package.source, "ghcr.io/webassembly");
+        assert_eq!(package.digest, digest(b"package bytes"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn load_returns_none_when_no_lockfile_exists() {
+        let dir = std::env::temp_dir().join(format!(
+            "componentize-go-lockfile-test-missing-{:?}",
+            std::thread::current().id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        assert!(Lockfile::load(&dir).unwrap().is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
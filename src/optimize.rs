@@ -0,0 +1,444 @@
+use anyhow::{Context, Result, anyhow};
+use std::collections::{HashMap, HashSet};
+use wasmparser::{ConstExpr, ElementItems, ExternalKind, Operator, Parser, Payload, TypeRef, ValType};
+
+/// Custom sections that must survive optimization because later stages (or
+/// runtimes) depend on them, e.g. the `component-type` section that
+/// `embed_component_metadata` wrote.
+const PROTECTED_CUSTOM_SECTIONS: &[&str] = &["component-type"];
+
+/// Shrink an encoded core module by dropping functions unreachable from
+/// its exports (and the WASI adapter entry points) and stripping non-essential
+/// custom sections such as debug info.
+///
+/// `strip` controls whether custom sections are stripped; dead-function
+/// elimination always runs since it cannot change observable behavior.
+pub fn optimize(wasm: &[u8], strip: bool) -> Result<Vec<u8>> {
+    let pruned = eliminate_dead_functions(wasm)?;
+    if strip {
+        strip_custom_sections(&pruned)
+    } else {
+        Ok(pruned)
+    }
+}
+
+/// Compute the set of functions reachable from `roots` by following `edges`
+/// (caller index -> direct-call callee indices). Imported functions have no
+/// entry in `edges` (no body to walk) and are simply leaves.
+fn reachable(roots: impl IntoIterator<Item = u32>, edges: &HashMap<u32, Vec<u32>>) -> HashSet<u32> {
+    let mut seen = HashSet::new();
+    let mut stack: Vec<u32> = roots.into_iter().collect();
+    while let Some(f) = stack.pop() {
+        if seen.insert(f) {
+            if let Some(callees) = edges.get(&f) {
+                stack.extend(callees.iter().copied());
+            }
+        }
+    }
+    seen
+}
+
+/// Walk `wasm`'s code section to find every function reachable from its
+/// exports (which include the WASI adapter's entry points, e.g. `_start` and
+/// `cabi_realloc`, since those are exported from the core module) or from an
+/// element segment (since `call_indirect` could dispatch to anything placed
+/// in a table). Returns `(number of imported functions, total reachable set)`.
+fn reachable_functions(wasm: &[u8]) -> Result<(u32, HashSet<u32>)> {
+    let mut num_func_imports = 0u32;
+    let mut roots = HashSet::new();
+    let mut edges: HashMap<u32, Vec<u32>> = HashMap::new();
+    let mut next_func_idx = 0u32;
+
+    for payload in Parser::new(0).parse_all(wasm) {
+        match payload.context("failed to parse module for dead-function elimination")? {
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    if matches!(import?.ty, TypeRef::Func(_)) {
+                        num_func_imports += 1;
+                    }
+                }
+                next_func_idx = num_func_imports;
+            }
+            Payload::ExportSection(reader) => {
+                for export in reader {
+                    let export = export?;
+                    if export.kind == ExternalKind::Func {
+                        roots.insert(export.index);
+                    }
+                }
+            }
+            Payload::ElementSection(reader) => {
+                for element in reader {
+                    if let ElementItems::Functions(funcs) = element?.items {
+                        for f in funcs {
+                            roots.insert(f?);
+                        }
+                    }
+                }
+            }
+            Payload::CodeSectionEntry(body) => {
+                let func_idx = next_func_idx;
+                next_func_idx += 1;
+                let mut callees = Vec::new();
+                for op in body.get_operators_reader()? {
+                    if let Operator::Call { function_index } = op? {
+                        callees.push(function_index);
+                    }
+                }
+                edges.insert(func_idx, callees);
+            }
+            _ => {}
+        }
+    }
+
+    Ok((num_func_imports, reachable(roots, &edges)))
+}
+
+/// Drop local functions unreachable from the module's exports/element
+/// segments, compacting the function index space and rewriting every
+/// `call`, export and element reference to match. Imported functions are
+/// never pruned, since removing one would change what the module requires
+/// its embedder to provide.
+///
+/// Element segments encoded with explicit init-expression items (rather than
+/// a plain function index list) are not supported, since Go's toolchain
+/// doesn't emit them; a module that has them and needs pruning fails to
+/// re-encode with an error rather than silently losing the functions they
+/// reference.
+fn eliminate_dead_functions(wasm: &[u8]) -> Result<Vec<u8>> {
+    let (num_func_imports, reachable) = reachable_functions(wasm)?;
+
+    // Map every kept function's old absolute index to its new one. Imports
+    // keep their index; reachable locals are compacted in original order.
+    let mut remap: HashMap<u32, u32> = (0..num_func_imports).map(|i| (i, i)).collect();
+    let mut next_idx = num_func_imports;
+    let mut total_funcs = num_func_imports;
+    for payload in Parser::new(0).parse_all(wasm) {
+        if let Payload::FunctionSection(reader) = payload.context("failed to parse module")? {
+            for ty in reader {
+                ty?;
+                if reachable.contains(&total_funcs) {
+                    remap.insert(total_funcs, next_idx);
+                    next_idx += 1;
+                }
+                total_funcs += 1;
+            }
+        }
+    }
+
+    // Nothing to prune: skip the rewrite so an already-minimal module
+    // round-trips byte-for-byte.
+    if remap.len() as u32 == total_funcs {
+        return Ok(wasm.to_vec());
+    }
+
+    rewrite(wasm, &remap)
+}
+
+fn rewrite(wasm: &[u8], remap: &HashMap<u32, u32>) -> Result<Vec<u8>> {
+    let mut module = wasm_encoder::Module::new();
+    let mut next_func_idx = 0u32;
+    let mut num_func_imports_seen = 0u32;
+    let mut seen_imports = false;
+
+    for payload in Parser::new(0).parse_all(wasm) {
+        let payload = payload.context("failed to parse module for dead-function elimination")?;
+        match payload {
+            Payload::ImportSection(ref reader) => {
+                for import in reader.clone() {
+                    if matches!(import?.ty, TypeRef::Func(_)) {
+                        num_func_imports_seen += 1;
+                    }
+                }
+                next_func_idx = num_func_imports_seen;
+                seen_imports = true;
+                emit_raw(&mut module, wasm, &payload)?;
+            }
+            Payload::FunctionSection(reader) => {
+                debug_assert!(seen_imports, "imports precede functions in a valid module");
+                let mut section = wasm_encoder::FunctionSection::new();
+                let mut idx = num_func_imports_seen;
+                for ty in reader {
+                    let ty = ty?;
+                    if remap.contains_key(&idx) {
+                        section.function(ty);
+                    }
+                    idx += 1;
+                }
+                module.section(&section);
+            }
+            Payload::ExportSection(reader) => {
+                let mut section = wasm_encoder::ExportSection::new();
+                for export in reader {
+                    let export = export?;
+                    let index = if export.kind == ExternalKind::Func {
+                        *remap
+                            .get(&export.index)
+                            .ok_or_else(|| anyhow!("exported function '{}' was pruned", export.name))?
+                    } else {
+                        export.index
+                    };
+                    section.export(export.name, convert_export_kind(export.kind), index);
+                }
+                module.section(&section);
+            }
+            Payload::ElementSection(reader) => {
+                let mut section = wasm_encoder::ElementSection::new();
+                for element in reader {
+                    let element = element?;
+                    let remapped: Vec<u32> = match &element.items {
+                        ElementItems::Functions(funcs) => funcs
+                            .clone()
+                            .into_iter()
+                            .map(|f| {
+                                let f = f?;
+                                remap
+                                    .get(&f)
+                                    .copied()
+                                    .ok_or_else(|| anyhow!("element-referenced function {f} was pruned"))
+                            })
+                            .collect::<Result<Vec<u32>>>()?,
+                        ElementItems::Expressions(..) => {
+                            return Err(anyhow!(
+                                "--optimize does not yet support element segments encoded as expressions"
+                            ));
+                        }
+                    };
+                    let elements = wasm_encoder::Elements::Functions(&remapped);
+                    match element.kind {
+                        wasmparser::ElementKind::Active {
+                            table_index,
+                            offset_expr,
+                        } => {
+                            let offset = convert_const_expr(&offset_expr)?;
+                            section.active(table_index, &offset, elements);
+                        }
+                        wasmparser::ElementKind::Passive => {
+                            section.passive(elements);
+                        }
+                        wasmparser::ElementKind::Declared => {
+                            section.declared(elements);
+                        }
+                    }
+                }
+                module.section(&section);
+            }
+            Payload::CodeSectionEntry(body) => {
+                let func_idx = next_func_idx;
+                next_func_idx += 1;
+                if !remap.contains_key(&func_idx) {
+                    continue;
+                }
+                module.section(&rewritten_code_entry(wasm, &body, remap)?);
+            }
+            Payload::End(_) => {}
+            other => emit_raw(&mut module, wasm, &other)?,
+        }
+    }
+
+    Ok(module.finish())
+}
+
+/// Copy a section through unchanged, by byte range.
+fn emit_raw(module: &mut wasm_encoder::Module, wasm: &[u8], payload: &Payload) -> Result<()> {
+    if let Some((id, range)) = payload.as_section() {
+        module.section(&wasm_encoder::RawSection {
+            id,
+            data: &wasm[range],
+        });
+    }
+    Ok(())
+}
+
+/// Build a single-function `wasm_encoder::CodeSection` with every `call`
+/// target rewritten through `remap`.
+fn rewritten_code_entry(
+    wasm: &[u8],
+    body: &wasmparser::FunctionBody,
+    remap: &HashMap<u32, u32>,
+) -> Result<wasm_encoder::CodeSection> {
+    let mut locals = Vec::new();
+    let mut locals_reader = body.get_locals_reader()?;
+    for _ in 0..locals_reader.get_count() {
+        let (count, ty) = locals_reader.read()?;
+        locals.push((count, convert_val_type(ty)));
+    }
+
+    // Rebuild the operator stream forward rather than splicing re-encoded
+    // operands into a copy of the original bytes: a remapped index almost
+    // never LEB128-encodes to the same byte length as the original, so any
+    // in-place splice desyncs every later operator's offsets from the
+    // (now wrong-length) buffer.
+    let operators_start = body.get_operators_reader()?.original_position();
+    let mut raw = Vec::new();
+    let mut cursor = operators_start;
+
+    let mut reader = body.get_operators_reader()?;
+    while !reader.eof() {
+        let op_start = reader.original_position();
+        let op = reader.read()?;
+        let op_end = reader.original_position();
+        if let Operator::Call { function_index } = op {
+            let new_index = *remap
+                .get(&function_index)
+                .ok_or_else(|| anyhow!("called function {function_index} was pruned"))?;
+            // The opcode byte is unchanged; only the trailing LEB128 operand
+            // is re-encoded, so copy through the opcode and replace the rest.
+            raw.extend_from_slice(&wasm[cursor..op_start + 1]);
+            leb128_u32(new_index, &mut raw);
+        } else {
+            raw.extend_from_slice(&wasm[cursor..op_end]);
+        }
+        cursor = op_end;
+    }
+
+    let mut function = wasm_encoder::Function::new(locals);
+    function.raw(raw);
+    let mut code = wasm_encoder::CodeSection::new();
+    code.function(&function);
+    Ok(code)
+}
+
+fn leb128_u32(mut value: u32, out: &mut Vec<u8>) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value != 0 {
+            out.push(byte | 0x80);
+        } else {
+            out.push(byte);
+            break;
+        }
+    }
+}
+
+fn convert_export_kind(kind: ExternalKind) -> wasm_encoder::ExportKind {
+    match kind {
+        ExternalKind::Func => wasm_encoder::ExportKind::Func,
+        ExternalKind::Table => wasm_encoder::ExportKind::Table,
+        ExternalKind::Memory => wasm_encoder::ExportKind::Memory,
+        ExternalKind::Global => wasm_encoder::ExportKind::Global,
+        ExternalKind::Tag => wasm_encoder::ExportKind::Tag,
+    }
+}
+
+fn convert_val_type(ty: ValType) -> wasm_encoder::ValType {
+    match ty {
+        ValType::I32 => wasm_encoder::ValType::I32,
+        ValType::I64 => wasm_encoder::ValType::I64,
+        ValType::F32 => wasm_encoder::ValType::F32,
+        ValType::F64 => wasm_encoder::ValType::F64,
+        ValType::V128 => wasm_encoder::ValType::V128,
+        ValType::Ref(r) => wasm_encoder::ValType::Ref(wasm_encoder::RefType {
+            nullable: r.is_nullable(),
+            heap_type: wasm_encoder::HeapType::Func,
+        }),
+    }
+}
+
+/// Convert the handful of constant-expression forms Go/`wasm-ld` actually
+/// emit for element-segment table offsets.
+fn convert_const_expr(expr: &ConstExpr) -> Result<wasm_encoder::ConstExpr> {
+    let mut reader = expr.get_operators_reader();
+    let op = reader.read()?;
+    match op {
+        Operator::I32Const { value } => Ok(wasm_encoder::ConstExpr::i32_const(value)),
+        Operator::GlobalGet { global_index } => Ok(wasm_encoder::ConstExpr::global_get(global_index)),
+        other => Err(anyhow!("unsupported element-offset expression {other:?}")),
+    }
+}
+
+/// Re-encode `wasm`, dropping the custom sections that
+/// [`should_keep_custom_section`] rejects (`producers` and `.debug_*`).
+fn strip_custom_sections(wasm: &[u8]) -> Result<Vec<u8>> {
+    let mut output = wasm_encoder::Module::new();
+
+    for payload in Parser::new(0).parse_all(wasm) {
+        let payload = payload.context("failed to parse module for section stripping")?;
+        match payload {
+            Payload::CustomSection(reader) => {
+                if should_keep_custom_section(reader.name()) {
+                    output.section(&wasm_encoder::CustomSection {
+                        name: reader.name().into(),
+                        data: reader.data().into(),
+                    });
+                }
+            }
+            Payload::End(_) => {}
+            other => {
+                if let Some((id, range)) = other.as_section() {
+                    output.section(&wasm_encoder::RawSection {
+                        id,
+                        data: &wasm[range],
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(output.finish())
+}
+
+/// Whether a custom section named `name` should survive stripping: sections
+/// in [`PROTECTED_CUSTOM_SECTIONS`] are always kept, debug info and
+/// `producers` are always dropped, and anything else is kept by default.
+fn should_keep_custom_section(name: &str) -> bool {
+    let protected = PROTECTED_CUSTOM_SECTIONS.iter().any(|p| name.starts_with(p));
+    let strippable = name == "producers" || name.starts_with(".debug_");
+    protected || !strippable
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{reachable, should_keep_custom_section};
+    use std::collections::HashMap;
+
+    #[test]
+    fn reachable_follows_call_edges_transitively() {
+        let mut edges = HashMap::new();
+        edges.insert(0, vec![1]);
+        edges.insert(1, vec![2]);
+        edges.insert(2, vec![]);
+        edges.insert(3, vec![4]); // unreachable island
+
+        let seen = reachable([0], &edges);
+        assert_eq!(seen, [0, 1, 2].into_iter().collect());
+    }
+
+    #[test]
+    fn reachable_stops_at_leaves_with_no_edges() {
+        // Imported functions have no entry in `edges` at all.
+        let edges = HashMap::new();
+        let seen = reachable([7], &edges);
+        assert_eq!(seen, [7].into_iter().collect());
+    }
+
+    #[test]
+    fn reachable_handles_cycles() {
+        let mut edges = HashMap::new();
+        edges.insert(0, vec![1]);
+        edges.insert(1, vec![0]);
+
+        let seen = reachable([0], &edges);
+        assert_eq!(seen, [0, 1].into_iter().collect());
+    }
+
+    #[test]
+    fn protected_sections_are_always_kept() {
+        assert!(should_keep_custom_section("component-type"));
+        assert!(should_keep_custom_section("component-type:wasi:cli/command"));
+    }
+
+    #[test]
+    fn debug_and_producers_sections_are_stripped() {
+        assert!(!should_keep_custom_section("producers"));
+        assert!(!should_keep_custom_section(".debug_info"));
+        assert!(!should_keep_custom_section(".debug_line"));
+    }
+
+    #[test]
+    fn unrelated_custom_sections_are_kept() {
+        assert!(should_keep_custom_section("name"));
+        assert!(should_keep_custom_section("some-other-section"));
+    }
+}
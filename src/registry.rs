@@ -0,0 +1,273 @@
+use crate::lockfile::{self, Lockfile, LockedPackage};
+use anyhow::{Context, Result, anyhow};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+use wit_parser::{PackageName, Resolve, UnresolvedPackageGroup};
+
+/// Maps WIT package namespaces (the `wasi` in `wasi:http`) to the OCI
+/// registry host that serves them, e.g. `wasi=ghcr.io/webassembly`.
+#[derive(Debug, Clone, Default)]
+pub struct RegistryMapping {
+    hosts: HashMap<String, String>,
+}
+
+impl RegistryMapping {
+    /// Parse `--registry namespace=host` values into a mapping.
+    pub fn parse(entries: &[String]) -> Result<Self> {
+        let mut hosts = HashMap::new();
+        for entry in entries {
+            let (namespace, host) = entry.split_once('=').ok_or_else(|| {
+                anyhow!("invalid --registry value '{entry}', expected 'namespace=host'")
+            })?;
+            hosts.insert(namespace.to_string(), host.to_string());
+        }
+        Ok(Self { hosts })
+    }
+
+    fn host_for(&self, namespace: &str) -> Option<&str> {
+        self.hosts.get(namespace).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_namespace_equals_host_pairs() {
+        let mapping = RegistryMapping::parse(&[
+            "wasi=ghcr.io/webassembly".to_string(),
+            "example=registry.example.com".to_string(),
+        ])
+        .unwrap();
+
+        assert_eq!(mapping.host_for("wasi"), Some("ghcr.io/webassembly"));
+        assert_eq!(mapping.host_for("example"), Some("registry.example.com"));
+    }
+
+    #[test]
+    fn empty_input_yields_no_mappings() {
+        let mapping = RegistryMapping::parse(&[]).unwrap();
+        assert_eq!(mapping.host_for("wasi"), None);
+    }
+
+    #[test]
+    fn unmapped_namespace_yields_none() {
+        let mapping = RegistryMapping::parse(&["wasi=ghcr.io/webassembly".to_string()]).unwrap();
+        assert_eq!(mapping.host_for("other"), None);
+    }
+
+    #[test]
+    fn rejects_an_entry_without_an_equals_sign() {
+        assert!(RegistryMapping::parse(&["wasi".to_string()]).is_err());
+    }
+
+    #[test]
+    fn should_update_is_false_when_the_flag_is_absent() {
+        let name: PackageName = "wasi:http".parse().unwrap();
+        assert!(!should_update(None, &name));
+    }
+
+    #[test]
+    fn should_update_updates_everything_when_no_names_are_given() {
+        let name: PackageName = "wasi:http".parse().unwrap();
+        assert!(should_update(Some(&[]), &name));
+    }
+
+    #[test]
+    fn should_update_matches_only_named_packages() {
+        let http: PackageName = "wasi:http".parse().unwrap();
+        let cli: PackageName = "wasi:cli".parse().unwrap();
+        let names = ["wasi:http".to_string()];
+
+        assert!(should_update(Some(&names), &http));
+        assert!(!should_update(Some(&names), &cli));
+    }
+}
+
+/// Parse every WIT document in `paths`, fetching any package it depends on
+/// that isn't present on disk from the registries in `mapping`, then push
+/// everything into `resolve`.
+///
+/// Resolved packages are pinned in `componentize-go.lock` next to
+/// `mod_path`'s `go.mod`, and cached under `.componentize-go/wit-cache` so a
+/// re-run doesn't refetch a package whose pinned digest already matches what's
+/// on disk. If `frozen` is set, resolution fails rather than adding a new
+/// entry or changing an existing one. `update` forces a fresh fetch (ignoring
+/// both the cache and the existing pin) for the named packages, or for every
+/// missing package if it's `Some(&[])`.
+pub fn resolve_paths(
+    resolve: &mut Resolve,
+    paths: &[PathBuf],
+    mapping: &RegistryMapping,
+    mod_path: &Path,
+    frozen: bool,
+    update: Option<&[String]>,
+) -> Result<Vec<wit_parser::PackageId>> {
+    let mut lockfile = Lockfile::load(mod_path)?.unwrap_or_default();
+    let mut dirty = false;
+
+    // What each of `paths` defines itself (including nested packages, e.g.
+    // a `deps` subfolder `push_path` would auto-discover) is satisfied
+    // locally and must not be treated as a registry candidate, even though
+    // `resolve` itself is still empty at this point (nothing has been
+    // pushed into it yet — that happens below, in the second pass). This
+    // is exactly the layout `Common::wit_path`'s own doc comment calls out:
+    // `-d ./wit/deps -d ./wit/app` relies on `wit/deps` packages satisfying
+    // `wit/app`'s foreign deps without ever touching a registry.
+    let mut groups = Vec::with_capacity(paths.len());
+    let mut defined: HashSet<PackageName> = HashSet::new();
+    for path in paths {
+        let group = UnresolvedPackageGroup::parse_path(path)
+            .context(format!("failed to parse '{}'", path.display()))?;
+        defined.insert(group.main.name.clone());
+        defined.extend(group.nested.iter().map(|pkg| pkg.name.clone()));
+        groups.push(group);
+    }
+
+    let mut missing: Vec<PackageName> = Vec::new();
+    for group in &groups {
+        for name in group.main.foreign_deps.keys() {
+            if !defined.contains(name) && !missing.contains(name) {
+                missing.push(name.clone());
+            }
+        }
+        for nested in &group.nested {
+            for name in nested.foreign_deps.keys() {
+                if !defined.contains(name) && !missing.contains(name) {
+                    missing.push(name.clone());
+                }
+            }
+        }
+    }
+
+    let cache_dir = mod_path.join(".componentize-go").join("wit-cache");
+    for name in &missing {
+        let namespace = &name.namespace;
+        let host = mapping.host_for(namespace).ok_or_else(|| {
+            anyhow!("no registry configured for namespace '{namespace}' (use --registry {namespace}=<host>)")
+        })?;
+
+        // `--update` forces this package to be re-resolved from scratch,
+        // ignoring both the cache and its existing pin (if any).
+        let updating = should_update(update, name);
+        let locked = if updating {
+            None
+        } else {
+            lockfile.get(&name.to_string()).cloned()
+        };
+        if frozen && locked.is_none() {
+            return Err(anyhow!(
+                "'{name}' is not pinned in {}, but --frozen was given",
+                lockfile::FILE_NAME
+            ));
+        }
+
+        let version = locked
+            .as_ref()
+            .map(|p| p.version.clone())
+            .or_else(|| name.version.as_ref().map(|v| v.to_string()))
+            .ok_or_else(|| anyhow!("no version available to resolve '{name}'; pin one in WIT"))?;
+
+        // Namespace and name both key the cache file: two packages can share
+        // a bare name across namespaces (e.g. `wasi:http` vs `other:http`).
+        let cached_path = cache_dir.join(format!("{}-{}-{version}.wasm", name.namespace, name.name));
+        let cached = locked.as_ref().and_then(|locked| {
+            let bytes = std::fs::read(&cached_path).ok()?;
+            (lockfile::digest(&bytes) == locked.digest).then_some(bytes)
+        });
+
+        let bytes = match cached {
+            Some(bytes) => bytes,
+            None => {
+                let bytes = fetch(host, name, &version)
+                    .context(format!("failed to fetch '{name}@{version}' from '{host}'"))?;
+                std::fs::create_dir_all(&cache_dir)
+                    .context(format!("failed to create '{}'", cache_dir.display()))?;
+                std::fs::write(&cached_path, &bytes)
+                    .context(format!("failed to write '{}'", cached_path.display()))?;
+                bytes
+            }
+        };
+        let digest = lockfile::digest(&bytes);
+
+        if let Some(locked) = &locked {
+            if locked.digest != digest {
+                return Err(anyhow!(
+                    "digest mismatch for '{name}@{version}': {} recorded '{}', registry returned '{digest}'",
+                    lockfile::FILE_NAME,
+                    locked.digest
+                ));
+            }
+        } else {
+            if frozen {
+                return Err(anyhow!(
+                    "resolving '{name}' would add an entry to {}, but --frozen was given",
+                    lockfile::FILE_NAME
+                ));
+            }
+            lockfile.insert(
+                name.to_string(),
+                LockedPackage {
+                    version: version.clone(),
+                    source: format!("{host}/{name}"),
+                    digest,
+                },
+            );
+            dirty = true;
+        }
+
+        resolve.push_path(&cached_path)?;
+    }
+
+    if dirty {
+        lockfile.write(mod_path)?;
+    }
+
+    let mut pushed = Vec::new();
+    for path in paths {
+        let (pkg, _files) = resolve.push_path(path)?;
+        pushed.push(pkg);
+    }
+    Ok(pushed)
+}
+
+/// Whether `name` should be force-updated per `--update`: `None` means the
+/// flag wasn't given, `Some(&[])` means every missing package should update,
+/// and `Some(names)` updates only those listed (matched by `namespace:name`).
+fn should_update(update: Option<&[String]>, name: &PackageName) -> bool {
+    match update {
+        None => false,
+        Some(names) if names.is_empty() => true,
+        Some(names) => {
+            let unversioned = format!("{}:{}", name.namespace, name.name);
+            names.iter().any(|n| *n == unversioned)
+        }
+    }
+}
+
+/// Fetch a single package's bytes from an OCI-backed WIT registry.
+fn fetch(host: &str, name: &PackageName, version: &str) -> Result<Vec<u8>> {
+    let package_ref: wasm_pkg_client::PackageRef = format!("{}:{}", name.namespace, name.name)
+        .parse()
+        .context("invalid package reference")?;
+    let version: semver::Version = version.parse().context("invalid package version")?;
+
+    let runtime = tokio::runtime::Runtime::new().context("failed to start async runtime")?;
+    runtime.block_on(async {
+        let client = wasm_pkg_client::Client::new(wasm_pkg_client::Config::default_with_registry(
+            host.parse().context("invalid registry host")?,
+        ));
+        let release = client
+            .get_release(&package_ref, &version)
+            .await
+            .context("failed to fetch release metadata")?;
+        client
+            .get_content(&package_ref, &release)
+            .await
+            .map(|bytes| bytes.to_vec())
+            .context("failed to download package content")
+    })
+}
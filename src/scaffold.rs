@@ -0,0 +1,153 @@
+use anyhow::{Context, Result, anyhow};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Which flavor of skeleton to generate for `new`.
+#[derive(Clone, Copy, Debug, Default, clap::ValueEnum)]
+pub enum Template {
+    /// A world exporting a single plain `run` function.
+    #[default]
+    Wasip2,
+    /// A `wasi:http/incoming-handler@0.3.0` export, targeting `wasip3`.
+    Wasip3,
+    /// A `wasi:http/incoming-handler` export, matching the `serve` examples.
+    Http,
+}
+
+impl std::fmt::Display for Template {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Template::Wasip2 => write!(f, "wasip2"),
+            Template::Wasip3 => write!(f, "wasip3"),
+            Template::Http => write!(f, "http"),
+        }
+    }
+}
+
+/// Minimum Go version required by the embedded Wasm tooling, matching
+/// `check_go_version`'s `^1.25` requirement.
+const GO_VERSION: &str = "1.25.0";
+
+/// Scaffold a ready-to-build Go component project under `dir`: a `go.mod`
+/// pinned to a compatible Go toolchain, a `wit/world.wit` skeleton named
+/// `world`, and a `main.go` exporting it, then run `go mod tidy`.
+pub fn new(dir: &Path, module: &str, world: &str, template: Template, go: Option<&PathBuf>) -> Result<()> {
+    std::fs::create_dir_all(dir.join("wit"))
+        .context(format!("failed to create '{}'", dir.join("wit").display()))?;
+
+    std::fs::write(dir.join("go.mod"), go_mod(module))
+        .context(format!("failed to write '{}'", dir.join("go.mod").display()))?;
+
+    std::fs::write(dir.join("wit").join("world.wit"), world_wit(world, template))
+        .context(format!(
+            "failed to write '{}'",
+            dir.join("wit/world.wit").display()
+        ))?;
+
+    std::fs::write(dir.join("main.go"), main_go(template))
+        .context(format!("failed to write '{}'", dir.join("main.go").display()))?;
+
+    let go_path = go.cloned().unwrap_or_else(|| PathBuf::from("go"));
+    let tidy_output = Command::new(&go_path)
+        .arg("mod")
+        .arg("tidy")
+        .current_dir(dir)
+        .output()
+        .context(format!("failed to execute '{}'", go_path.display()))?;
+
+    if !tidy_output.status.success() {
+        return Err(anyhow!(
+            "'go mod tidy' failed: {}",
+            String::from_utf8_lossy(&tidy_output.stderr)
+        ));
+    }
+
+    Ok(())
+}
+
+fn go_mod(module: &str) -> String {
+    format!("module {module}\n\ngo {GO_VERSION}\n")
+}
+
+fn world_wit(world: &str, template: Template) -> String {
+    match template {
+        Template::Wasip2 => format!("package component:app;\n\nworld {world} {{\n    export run: func();\n}}\n"),
+        // wasip3 only exists as an async proposal so far, surfaced through
+        // `wasi:http`'s `@0.3.0` interfaces; there's no async `wasi:cli/run` yet.
+        Template::Wasip3 => format!(
+            "package component:app;\n\nworld {world} {{\n    export wasi:http/incoming-handler@0.3.0;\n}}\n"
+        ),
+        Template::Http => format!(
+            "package component:app;\n\nworld {world} {{\n    export wasi:http/incoming-handler@0.2.0;\n}}\n"
+        ),
+    }
+}
+
+fn main_go(template: Template) -> String {
+    match template {
+        Template::Wasip2 => "package main\n\nfunc main() {}\n\nfunc Run() {}\n".to_string(),
+        Template::Wasip3 => r#"package main
+
+import (
+	"net/http"
+)
+
+// The generated bindings adapt this handler to `wasi:http`'s async
+// `@0.3.0` incoming-handler for you; this code stays synchronous Go.
+func init() {
+	http.HandleFunc("/hello", func(w http.ResponseWriter, r *http.Request) {
+		w.Write([]byte("Hello, world!"))
+	})
+}
+
+func main() {}
+"#
+        .to_string(),
+        Template::Http => r#"package main
+
+import (
+	"net/http"
+)
+
+func init() {
+	http.HandleFunc("/", func(w http.ResponseWriter, r *http.Request) {
+		w.Write([]byte("Hello, world!"))
+	})
+}
+
+func main() {}
+"#
+        .to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn go_mod_pins_the_required_go_version() {
+        assert_eq!(go_mod("example.com/app"), "module example.com/app\n\ngo 1.25.0\n");
+    }
+
+    #[test]
+    fn each_template_generates_distinct_world_and_main() {
+        let templates = [Template::Wasip2, Template::Wasip3, Template::Http];
+        let worlds: Vec<String> = templates.iter().map(|t| world_wit("app", *t)).collect();
+        let mains: Vec<String> = templates.iter().map(|t| main_go(*t)).collect();
+
+        for i in 0..templates.len() {
+            for j in (i + 1)..templates.len() {
+                assert_ne!(worlds[i], worlds[j], "{:?} and {:?} share a world skeleton", templates[i], templates[j]);
+                assert_ne!(mains[i], mains[j], "{:?} and {:?} share a main.go skeleton", templates[i], templates[j]);
+            }
+        }
+    }
+
+    #[test]
+    fn wasip3_targets_the_async_http_interface() {
+        assert!(world_wit("app", Template::Wasip3).contains("@0.3.0"));
+    }
+}